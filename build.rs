@@ -0,0 +1,11 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+
+    let descriptor_path = std::path::PathBuf::from(std::env::var("OUT_DIR")?).join("catalog_descriptor.bin");
+
+    tonic_build::configure()
+        .file_descriptor_set_path(descriptor_path)
+        .compile(&["proto/catalog.proto"], &["proto"])?;
+
+    Ok(())
+}
@@ -0,0 +1,206 @@
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use rand::Rng;
+use reqwest::{Client, Error, Response, StatusCode};
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+
+const MAX_RETRY_ATTEMPTS: u32 = 5;
+const BASE_BACKOFF_MS: u64 = 250;
+const MAX_BACKOFF_MS: u64 = 10_000;
+
+// Burst capacity and steady-state throughput for the shared token bucket. Databricks' per-workspace
+// Unity Catalog read limits are generous but not unlimited, so keep concurrent refresh jobs under
+// them rather than leaning entirely on retry-after-429.
+const RATE_LIMIT_BURST: f64 = 10.0;
+const RATE_LIMIT_PER_SECOND: f64 = 5.0;
+
+#[derive(Clone)]
+pub struct APIClient {
+    pub db_token: String,
+    pub workspace_name: String,
+    client: Client,
+    rate_limiter: Arc<RateLimiter>,
+    max_retry_attempts: u32,
+}
+
+impl APIClient {
+    pub fn new(db_token: String, workspace_name: String) -> Self {
+        Self {
+            db_token,
+            workspace_name,
+            client: Client::new(),
+            rate_limiter: Arc::new(RateLimiter::new(RATE_LIMIT_BURST, RATE_LIMIT_PER_SECOND)),
+            max_retry_attempts: MAX_RETRY_ATTEMPTS,
+        }
+    }
+
+    // Overrides the default `MAX_RETRY_ATTEMPTS` ceiling for this client, e.g. from an
+    // `API_MAX_RETRY_ATTEMPTS` env var so an operator can trade off sync latency against
+    // resilience to a flaky workspace without a rebuild.
+    pub fn with_max_retry_attempts(mut self, max_retry_attempts: u32) -> Self {
+        self.max_retry_attempts = max_retry_attempts;
+        self
+    }
+
+    // GET `url` with the workspace bearer token, retrying on 429/5xx with jittered exponential
+    // backoff (honoring `Retry-After` on a 429) up to `max_retry_attempts`. Every attempt, including
+    // the first, first waits on the shared token-bucket rate limiter so concurrent refresh jobs
+    // can't collectively exceed the workspace's request budget.
+    pub async fn fetch(&self, url: String) -> Result<Response, Error> {
+        let mut attempt: u32 = 0;
+
+        loop {
+            self.rate_limiter.acquire().await;
+
+            let response: Response = self.client
+                .get(&url)
+                .bearer_auth(&self.db_token)
+                .send()
+                .await?;
+
+            let status: StatusCode = response.status();
+            if status.is_success() {
+                return Ok(response);
+            }
+
+            attempt += 1;
+            let retryable: bool = status == StatusCode::TOO_MANY_REQUESTS || status.is_server_error();
+            if !retryable || attempt > self.max_retry_attempts {
+                log::error!("Giving up on {} after {} attempt(s): {}", url, attempt, status);
+                return response.error_for_status();
+            }
+
+            let delay: Duration = if status == StatusCode::TOO_MANY_REQUESTS {
+                retry_after_delay(response.headers()).unwrap_or_else(|| backoff_delay(attempt))
+            } else {
+                backoff_delay(attempt)
+            };
+
+            log::warn!(
+                "Request to {} returned {} (attempt {}/{}), retrying in {:?}",
+                url, status, attempt, self.max_retry_attempts, delay
+            );
+            sleep(delay).await;
+        }
+    }
+}
+
+fn retry_after_delay(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    headers
+        .get(reqwest::header::RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(Duration::from_secs)
+}
+
+fn backoff_delay(attempt: u32) -> Duration {
+    let exponential_ms: u64 = BASE_BACKOFF_MS.saturating_mul(1u64 << attempt.saturating_sub(1).min(16));
+    let capped_ms: u64 = exponential_ms.min(MAX_BACKOFF_MS);
+    let jitter_ms: u64 = rand::thread_rng().gen_range(0..=capped_ms / 2);
+    Duration::from_millis(capped_ms / 2 + jitter_ms)
+}
+
+struct TokenBucketState {
+    tokens: f64,
+    last_refill: Instant,
+}
+
+// A simple async token bucket shared (via `Arc`) across every clone of an `APIClient`, so
+// concurrent `refresh_all_*` calls draw from one workspace-wide request budget instead of each
+// pacing itself independently.
+struct RateLimiter {
+    capacity: f64,
+    refill_per_second: f64,
+    state: Mutex<TokenBucketState>,
+}
+
+impl RateLimiter {
+    fn new(capacity: f64, refill_per_second: f64) -> Self {
+        Self {
+            capacity,
+            refill_per_second,
+            state: Mutex::new(TokenBucketState { tokens: capacity, last_refill: Instant::now() }),
+        }
+    }
+
+    async fn acquire(&self) {
+        loop {
+            let wait: Option<Duration> = {
+                let mut state = self.state.lock().await;
+
+                let now: Instant = Instant::now();
+                let elapsed: f64 = now.duration_since(state.last_refill).as_secs_f64();
+                state.tokens = (state.tokens + elapsed * self.refill_per_second).min(self.capacity);
+                state.last_refill = now;
+
+                if state.tokens >= 1.0 {
+                    state.tokens -= 1.0;
+                    None
+                } else {
+                    let shortfall: f64 = 1.0 - state.tokens;
+                    Some(Duration::from_secs_f64(shortfall / self.refill_per_second))
+                }
+            };
+
+            match wait {
+                None => return,
+                Some(duration) => sleep(duration).await,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_delay_grows_exponentially_and_caps() {
+        let first: Duration = backoff_delay(1);
+        assert!(first >= Duration::from_millis(125) && first <= Duration::from_millis(250));
+
+        let third: Duration = backoff_delay(3);
+        assert!(third >= Duration::from_millis(500) && third <= Duration::from_millis(1000));
+        assert!(third > first);
+
+        // Far beyond the point where the exponential would blow past MAX_BACKOFF_MS, the delay
+        // must still be clamped to it rather than growing unbounded.
+        let capped: Duration = backoff_delay(20);
+        assert!(capped >= Duration::from_millis(MAX_BACKOFF_MS / 2));
+        assert!(capped <= Duration::from_millis(MAX_BACKOFF_MS));
+    }
+
+    #[test]
+    fn retry_after_delay_parses_the_header() {
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(reqwest::header::RETRY_AFTER, "30".parse().unwrap());
+        assert_eq!(retry_after_delay(&headers), Some(Duration::from_secs(30)));
+    }
+
+    #[test]
+    fn retry_after_delay_ignores_missing_or_unparseable_header() {
+        let empty = reqwest::header::HeaderMap::new();
+        assert_eq!(retry_after_delay(&empty), None);
+
+        let mut non_numeric = reqwest::header::HeaderMap::new();
+        non_numeric.insert(reqwest::header::RETRY_AFTER, "Wed, 21 Oct 2015 07:28:00 GMT".parse().unwrap());
+        assert_eq!(retry_after_delay(&non_numeric), None);
+    }
+
+    #[tokio::test]
+    async fn rate_limiter_acquire_drains_and_refills_the_bucket() {
+        let limiter = RateLimiter::new(2.0, 1000.0);
+
+        // Burst capacity lets the first two calls through without waiting.
+        let start = Instant::now();
+        limiter.acquire().await;
+        limiter.acquire().await;
+        assert!(start.elapsed() < Duration::from_millis(50));
+
+        // The bucket is now empty, so a third call must wait for a refill.
+        limiter.acquire().await;
+        assert!(start.elapsed() >= Duration::from_millis(1));
+    }
+}
@@ -1,9 +1,31 @@
+use futures::stream::{self, StreamExt, TryStreamExt};
 use reqwest::{Response, Error};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
 use super::api_client::APIClient;
 use crate::sql::sql_client::SqlClient as SQLClient;
 
+// How many catalogs (and, within a catalog, how many schemas) `refresh_all_schemas`/
+// `refresh_all_tables` fetch concurrently. `APIClient::fetch`'s shared token bucket is what
+// actually keeps concurrent jobs under the workspace's request budget, so this just bounds how
+// much work is in flight at once rather than how fast it runs.
+const CONCURRENT_CATALOG_JOBS: usize = 8;
+const CONCURRENT_SCHEMA_JOBS: usize = 8;
+
+// `catalog_type`/`name` exclusions shared by `refresh_all_schemas` and `refresh_all_tables`.
+fn is_syncable(catalog: &Catalog) -> bool {
+    catalog.catalog_type != "DELTASHARING_CATALOG"
+        && catalog.name != "__databricks_internal"
+        && catalog.name != "adrian_hive_test"
+}
+
+// Append a `page_token` query parameter to a (possibly already query-bearing) URL,
+// percent-encoding the token so opaque tokens with reserved characters survive the round trip.
+fn with_page_token(url: &str, token: &str) -> String {
+    let separator: char = if url.contains('?') { '&' } else { '?' };
+    format!("{}{}page_token={}", url, separator, urlencoding::encode(token))
+}
+
 
 
 #[derive(Clone)]
@@ -20,19 +42,36 @@ impl MetastoreClient {
     async fn fetch_catalogs(&self) -> Result<CatalogResponse, Error>  {
         let catalog_url: String = format!("https://{}/api/2.1/unity-catalog/catalogs", &self.api_client.workspace_name);
 
-        let response: Response = self.api_client.fetch(catalog_url).await?;
+        let mut catalogs: Vec<Catalog> = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let url: String = match &page_token {
+                Some(token) => with_page_token(&catalog_url, token),
+                None => catalog_url.clone(),
+            };
+
+            let response: Response = self.api_client.fetch(url).await?;
+
+            let page: CatalogResponse = match response.json().await {
+                Ok(page) => page, // If deserialization succeeds, continue with the deserialized data
+                Err(e) => {
+                    // If deserialization fails, log the error and return an error
+                    log::error!("Error deserializing JSON response: {}", e);
+                    return Err(e);
+                }
+
+            };
 
-        let catalogs: CatalogResponse = match response.json().await {
-            Ok(catalogs) => catalogs, // If deserialization succeeds, continue with the deserialized data
-            Err(e) => {
-                // If deserialization fails, log the error and return an error
-                log::error!("Error deserializing JSON response: {}", e);
-                return Err(e.into());
+            catalogs.extend(page.catalogs);
+
+            match page.next_page_token {
+                Some(token) if !token.is_empty() => page_token = Some(token),
+                _ => break,
             }
+        }
 
-        };
-        
-        Ok(catalogs)
+        Ok(CatalogResponse { catalogs, next_page_token: None })
     }
 
 
@@ -40,24 +79,43 @@ impl MetastoreClient {
     // https://docs.databricks.com/api/workspace/schemas/list
     async fn fetch_schemas(&self, catalog_name: String, max_results: Option<usize>) -> Result<SchemaResponse, Error>  {
         let mut schema_url = format!("https://{}/api/2.1/unity-catalog/schemas?catalog_name={}", &self.api_client.workspace_name, catalog_name);
-        
+
         if let Some(max) = max_results {
             schema_url.push_str(&format!("&max_results={}", max));
         }
-        
-        // Fetch schemas for the current catalog
-        let response: Response = self.api_client.fetch(schema_url).await?;
-        let schemas: SchemaResponse = match response.json().await {
-            Ok(schemas) => schemas, // If deserialization succeeds, continue with the deserialized data
-            Err(e) => {
-                // If deserialization fails, log the error and return an error
-                log::error!("Error deserializing JSON response: {}", e);
-                return Err(e.into());
+
+        let mut schemas: Vec<Schema> = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let url: String = match &page_token {
+                Some(token) => with_page_token(&schema_url, token),
+                None => schema_url.clone(),
+            };
+
+            // Fetch schemas for the current catalog
+            let response: Response = self.api_client.fetch(url).await?;
+            let page: SchemaResponse = match response.json().await {
+                Ok(page) => page, // If deserialization succeeds, continue with the deserialized data
+                Err(e) => {
+                    // If deserialization fails, log the error and return an error
+                    log::error!("Error deserializing JSON response: {}", e);
+                    return Err(e);
+                }
+
+            };
+
+            if let Some(page_schemas) = page.schemas {
+                schemas.extend(page_schemas);
             }
 
-        };
-        
-        Ok(schemas)
+            match page.next_page_token {
+                Some(token) if !token.is_empty() => page_token = Some(token),
+                _ => break,
+            }
+        }
+
+        Ok(SchemaResponse { schemas: Some(schemas), next_page_token: None })
     }
 
     // List all tables for a given schema/catalog in a Databricks' Unity Catalog Metastore
@@ -69,21 +127,38 @@ impl MetastoreClient {
             table_url.push_str(&format!("&max_results={}", max));
         }
 
-        // Fetch tables for the current catalog/schema
-        let response: Response = self.api_client.fetch(table_url.clone()).await?;
-        // let tables: TableResponse = response.json().await?;
-        let tables: TableResponse = match response.json().await {
-            Ok(tables) => tables, // If deserialization succeeds, continue with the deserialized data
-            Err(e) => {
-                // If deserialization fails, log the error and return an error
-                log::error!("Error deserializing JSON response: {}", e);
-                return Err(e.into());
+        let mut tables: Vec<Table> = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        loop {
+            let url: String = match &page_token {
+                Some(token) => with_page_token(&table_url, token),
+                None => table_url.clone(),
+            };
+
+            // Fetch tables for the current catalog/schema
+            let response: Response = self.api_client.fetch(url).await?;
+            let page: TableResponse = match response.json().await {
+                Ok(page) => page, // If deserialization succeeds, continue with the deserialized data
+                Err(e) => {
+                    // If deserialization fails, log the error and return an error
+                    log::error!("Error deserializing JSON response: {}", e);
+                    return Err(e);
+                }
+
+            };
+
+            if let Some(page_tables) = page.tables {
+                tables.extend(page_tables);
             }
 
-        };
-        
-        
-        Ok(tables)
+            match page.next_page_token {
+                Some(token) if !token.is_empty() => page_token = Some(token),
+                _ => break,
+            }
+        }
+
+        Ok(TableResponse { tables: Some(tables), next_page_token: None })
     }
 
     // Get an individual table object
@@ -107,43 +182,69 @@ impl MetastoreClient {
     }
 
 
+    // Fetches and caches the schemas of every syncable catalog, up to `CONCURRENT_CATALOG_JOBS`
+    // catalogs at a time, so large metastores don't pay for each catalog's round trips serially.
     pub async fn refresh_all_schemas(&self) -> Result<(), Error> {
         let catalogs: CatalogResponse = self.fetch_catalogs().await?;
         log::info!("Getting Schemas.");
-        for catalog in catalogs.catalogs {
-            // exclude delta sharing catalogs for now
-            if catalog.catalog_type != "DELTASHARING_CATALOG" && catalog.name != "__databricks_internal" && catalog.name != "adrian_hive_test" {
-                let schemas: SchemaResponse = self.fetch_schemas(catalog.name, None).await?;
-                self.sql_client.write_schemas(schemas).await.unwrap();
-                std::thread::sleep(std::time::Duration::from_secs(1));
-            }
-        }
+        let syncable: Vec<Catalog> = catalogs.catalogs.into_iter().filter(is_syncable).collect();
+
+        stream::iter(syncable)
+            .map(|catalog| {
+                let client: MetastoreClient = self.clone();
+                async move {
+                    let schemas: SchemaResponse = client.fetch_schemas(catalog.name, None).await?;
+                    client.sql_client.write_schemas(schemas).await.unwrap();
+                    Ok::<(), Error>(())
+                }
+            })
+            .buffer_unordered(CONCURRENT_CATALOG_JOBS)
+            .try_for_each(|_| async { Ok(()) })
+            .await?;
+
         Ok(())
     }
 
-
+    // Fetches and caches the tables of every syncable catalog. Catalogs are processed up to
+    // `CONCURRENT_CATALOG_JOBS` at a time, and within each catalog its schemas are fetched up to
+    // `CONCURRENT_SCHEMA_JOBS` at a time, so a metastore with many catalogs/schemas syncs in
+    // parallel instead of one schema's round trip at a time.
     pub async fn refresh_all_tables(&self) -> Result<(), Error> {
         let catalogs: CatalogResponse = self.fetch_catalogs().await?;
-        let num_cats = catalogs.catalogs.len();
-        let mut i = 0;
-        for catalog in catalogs.catalogs {
-            log::info!("Num catalogs: {} out of {}", i, num_cats);
-            i = i + 1;
-            if catalog.catalog_type != "DELTASHARING_CATALOG" && catalog.name != "__databricks_internal" && catalog.name != "adrian_hive_test" {
-                let schemas: SchemaResponse = self.fetch_schemas(catalog.name.clone(), None).await?;
-                if let Some(schemas) = schemas.schemas {
-                    for schema in schemas {
-                    log::info!("----------------> Getting Tables for Schema {}.{}.", schema.catalog_name, schema.name);
-                    let table_response = self.fetch_tables(catalog.name.clone(), schema.name, None).await?;
-                    if let Some(ref tables) = table_response.tables {
-                        log::info!("Num Tables: {}", tables.len());
-                        self.sql_client.write_tables(table_response).await.unwrap();
-                        // std::thread::sleep(std::time::Duration::from_secs(1));
+        let syncable: Vec<Catalog> = catalogs.catalogs.into_iter().filter(is_syncable).collect();
+        log::info!("Refreshing tables for {} catalogs", syncable.len());
+
+        stream::iter(syncable)
+            .map(|catalog| {
+                let client: MetastoreClient = self.clone();
+                async move {
+                    let schemas: SchemaResponse = client.fetch_schemas(catalog.name.clone(), None).await?;
+                    if let Some(schemas) = schemas.schemas {
+                        stream::iter(schemas)
+                            .map(|schema| {
+                                let client: MetastoreClient = client.clone();
+                                let catalog_name: String = catalog.name.clone();
+                                async move {
+                                    log::info!("----------------> Getting Tables for Schema {}.{}.", schema.catalog_name, schema.name);
+                                    let table_response: TableResponse = client.fetch_tables(catalog_name, schema.name, None).await?;
+                                    if let Some(ref tables) = table_response.tables {
+                                        log::info!("Num Tables: {}", tables.len());
+                                        client.sql_client.write_tables(table_response).await.unwrap();
+                                    }
+                                    Ok::<(), Error>(())
+                                }
+                            })
+                            .buffer_unordered(CONCURRENT_SCHEMA_JOBS)
+                            .try_for_each(|_| async { Ok(()) })
+                            .await?;
                     }
+                    Ok::<(), Error>(())
                 }
-            }
-            }
-        } 
+            })
+            .buffer_unordered(CONCURRENT_CATALOG_JOBS)
+            .try_for_each(|_| async { Ok(()) })
+            .await?;
+
         Ok(())
     }
 
@@ -152,14 +253,15 @@ impl MetastoreClient {
 }
 
 // wrapper struct to contain a vector of catalogs
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct CatalogResponse {
     pub catalogs: Vec<Catalog>,
+    pub next_page_token: Option<String>,
   }
 
 
 // individual struct for catalogs
-#[derive(Debug, Deserialize, Clone, FromRow)]
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
 pub struct Catalog {
     pub name: String,
     pub owner: String,
@@ -190,13 +292,14 @@ pub struct Catalog {
 
 
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct SchemaResponse {
     pub schemas: Option<Vec<Schema>>,
+    pub next_page_token: Option<String>,
   }
 
 
-#[derive(Debug, Deserialize, Clone, FromRow)]
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
 pub struct Schema {
     pub name: String,
     pub catalog_name: String,
@@ -218,13 +321,14 @@ pub struct Schema {
 }
 
 
-#[derive(Debug, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TableResponse {
     pub tables: Option<Vec<Table>>,
+    pub next_page_token: Option<String>,
   }
 
 
-#[derive(Debug, Deserialize, Clone, FromRow)]
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
 pub struct Table {
     pub name: String,
     pub catalog_name: String,
@@ -250,13 +354,109 @@ pub struct Table {
     pub access_point: Option<String>,
     pub pipeline_id: Option<String>,
     pub browse_only: Option<bool>,
+    // Nested payloads. `FromRow` can't map these from a flat SQL row since they're persisted into
+    // their own tables (see SqlClient::write_tables), so skip them there and rely on Default
+    // (`None`) when a `Table` is read back out of the cache instead of fetched from the API.
+    #[sqlx(skip)]
+    pub columns: Option<Vec<Column>>,
+    #[sqlx(skip)]
+    pub table_constraints: Option<Vec<TableConstraint>>,
+    #[sqlx(skip)]
+    pub dependencies: Option<Vec<TableDependency>>,
     // excluded fields due to nesting
-    // columns
-    // dependencies 
     // properties
-    // table_constraints
     // row_filter
     // delta_runtime_properties_kvpairs
     // effective_predictive_optimization_flag
 }
-    
\ No newline at end of file
+
+// A single column in a table's `information_schema`-style layout.
+// https://docs.databricks.com/api/workspace/tables/get
+#[derive(Debug, Serialize, Deserialize, Clone, FromRow)]
+pub struct Column {
+    pub name: String,
+    pub type_text: Option<String>,
+    pub type_name: Option<String>,
+    pub type_precision: Option<i64>,
+    pub type_scale: Option<i64>,
+    pub type_json: Option<String>,
+    pub position: Option<i64>,
+    pub comment: Option<String>,
+    pub nullable: Option<bool>,
+    pub partition_index: Option<i64>,
+}
+
+// One constraint from a table's `table_constraints` list. Databricks reports exactly one of the
+// three variants per entry; `constraint_type`/`constraint_name` are derived when persisting so
+// the flattened `table_constraints` SQL table stays queryable without a JSON column.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TableConstraint {
+    pub primary_key_constraint: Option<PrimaryKeyConstraint>,
+    pub foreign_key_constraint: Option<ForeignKeyConstraint>,
+    pub named_table_constraint: Option<NamedTableConstraint>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PrimaryKeyConstraint {
+    pub name: String,
+    pub child_columns: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ForeignKeyConstraint {
+    pub name: String,
+    pub child_columns: Vec<String>,
+    pub parent_table: String,
+    pub parent_columns: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct NamedTableConstraint {
+    pub name: String,
+}
+
+// One entry from a table's `dependencies` list (what the table, e.g. a view, reads from).
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TableDependency {
+    pub table: Option<TableDependencyRef>,
+    pub function: Option<FunctionDependencyRef>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TableDependencyRef {
+    pub table_full_name: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FunctionDependencyRef {
+    pub function_full_name: String,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn with_page_token_appends_to_a_plain_url() {
+        assert_eq!(
+            with_page_token("https://host/api/2.1/unity-catalog/catalogs", "tok"),
+            "https://host/api/2.1/unity-catalog/catalogs?page_token=tok"
+        );
+    }
+
+    #[test]
+    fn with_page_token_appends_to_an_already_query_bearing_url() {
+        assert_eq!(
+            with_page_token("https://host/api/2.1/unity-catalog/schemas?catalog_name=main", "tok"),
+            "https://host/api/2.1/unity-catalog/schemas?catalog_name=main&page_token=tok"
+        );
+    }
+
+    #[test]
+    fn with_page_token_percent_encodes_reserved_characters() {
+        assert_eq!(
+            with_page_token("https://host/x", "a&b=c"),
+            "https://host/x?page_token=a%26b%3Dc"
+        );
+    }
+}
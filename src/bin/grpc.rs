@@ -0,0 +1,27 @@
+use dotenv::dotenv;
+use std::env;
+use std::net::SocketAddr;
+
+use databricks_rust::grpc::server;
+use databricks_rust::sql::sql_client::SqlClient;
+
+// Serves the already-synced catalog cache over gRPC. Run `databricks-rust sync` first to
+// populate the database; this binary never talks to Databricks itself.
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    dotenv().ok();
+    env_logger::builder()
+        .filter_level(log::LevelFilter::Info)
+        .init();
+
+    let database_url: String = env::var("DATABASE_URL").expect("DATABASE_URL not set");
+    let migrations_path: String = env::var("MIGRATIONS_PATH").expect("MIGRATIONS_PATH not set");
+    let grpc_addr: String = env::var("GRPC_ADDR").unwrap_or_else(|_| "0.0.0.0:50051".to_string());
+
+    let sql_client: SqlClient = SqlClient::new(&database_url, migrations_path).await?;
+    let addr: SocketAddr = grpc_addr.parse()?;
+
+    server::serve(addr, sql_client).await?;
+
+    Ok(())
+}
@@ -0,0 +1,9 @@
+pub mod server;
+
+// Generated client/server stubs plus the encoded file descriptor set tonic-reflection serves so
+// tools like grpcurl can introspect the service without a local copy of catalog.proto.
+pub mod pb {
+    tonic::include_proto!("catalog");
+
+    pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("catalog_descriptor");
+}
@@ -0,0 +1,166 @@
+use std::net::SocketAddr;
+use tonic::{transport::Server, Request, Response, Status};
+
+use crate::sql::filter::Filters;
+use crate::sql::sql_client::{SearchLevel, SearchResults, SqlClient};
+use super::pb::{
+    unity_catalog_server::{UnityCatalog, UnityCatalogServer},
+    Catalog, GetTableRequest, ListCatalogsRequest, ListCatalogsResponse, ListSchemasRequest,
+    ListSchemasResponse, ListTablesRequest, ListTablesResponse, Schema, Table,
+    FILE_DESCRIPTOR_SET,
+};
+
+// Serves the locally-cached Unity Catalog metadata over gRPC. Every RPC reads straight from
+// `SqlClient`, never Databricks, so this is safe to point downstream engines at without
+// re-triggering a metastore sync.
+pub struct CatalogService {
+    pub sql_client: SqlClient,
+}
+
+#[tonic::async_trait]
+impl UnityCatalog for CatalogService {
+    async fn list_catalogs(&self, request: Request<ListCatalogsRequest>) -> Result<Response<ListCatalogsResponse>, Status> {
+        let name_contains = request.into_inner().name_contains;
+        let filters = Filters {
+            term: if name_contains.is_empty() { None } else { Some(name_contains) },
+            ..Default::default()
+        };
+
+        let catalogs = match self.sql_client.search(SearchLevel::Catalogs, filters).await
+            .map_err(|e| Status::internal(format!("failed to read catalogs: {}", e)))? {
+            SearchResults::Catalogs(catalogs) => catalogs,
+            _ => unreachable!("search(SearchLevel::Catalogs, ..) always returns SearchResults::Catalogs"),
+        };
+
+        let catalogs = catalogs.into_iter().map(|c| Catalog {
+            name: c.name,
+            owner: c.owner,
+            comment: c.comment.unwrap_or_default(),
+            catalog_type: c.catalog_type,
+            full_name: c.full_name,
+        }).collect();
+
+        Ok(Response::new(ListCatalogsResponse { catalogs }))
+    }
+
+    async fn list_schemas(&self, request: Request<ListSchemasRequest>) -> Result<Response<ListSchemasResponse>, Status> {
+        let catalog_name = request.into_inner().catalog_name;
+        let filters = Filters {
+            catalog_name: if catalog_name.is_empty() { None } else { Some(catalog_name) },
+            ..Default::default()
+        };
+
+        let schemas = match self.sql_client.search(SearchLevel::Schemas, filters).await
+            .map_err(|e| Status::internal(format!("failed to read schemas: {}", e)))? {
+            SearchResults::Schemas(schemas) => schemas,
+            _ => unreachable!("search(SearchLevel::Schemas, ..) always returns SearchResults::Schemas"),
+        };
+
+        let schemas = schemas.into_iter().map(|s| Schema {
+            name: s.name,
+            catalog_name: s.catalog_name,
+            owner: s.owner,
+            comment: s.comment.unwrap_or_default(),
+            full_name: s.full_name,
+        }).collect();
+
+        Ok(Response::new(ListSchemasResponse { schemas }))
+    }
+
+    async fn list_tables(&self, request: Request<ListTablesRequest>) -> Result<Response<ListTablesResponse>, Status> {
+        let req = request.into_inner();
+        let filters = Filters {
+            catalog_name: if req.catalog_name.is_empty() { None } else { Some(req.catalog_name) },
+            schema_name: if req.schema_name.is_empty() { None } else { Some(req.schema_name) },
+            ..Default::default()
+        };
+
+        let tables = match self.sql_client.search(SearchLevel::Tables, filters).await
+            .map_err(|e| Status::internal(format!("failed to read tables: {}", e)))? {
+            SearchResults::Tables(tables) => tables,
+            _ => unreachable!("search(SearchLevel::Tables, ..) always returns SearchResults::Tables"),
+        };
+
+        let tables = tables.into_iter().map(table_to_pb).collect();
+
+        Ok(Response::new(ListTablesResponse { tables }))
+    }
+
+    async fn get_table(&self, request: Request<GetTableRequest>) -> Result<Response<Table>, Status> {
+        let full_name = request.into_inner().full_name;
+        let (catalog_name, schema_name) = parse_full_name(&full_name)
+            .ok_or_else(|| Status::invalid_argument("full_name must be catalog.schema.table"))?;
+
+        let filters = Filters {
+            catalog_name: Some(catalog_name),
+            schema_name: Some(schema_name),
+            ..Default::default()
+        };
+        let tables = match self.sql_client.search(SearchLevel::Tables, filters).await
+            .map_err(|e| Status::internal(format!("failed to read tables: {}", e)))? {
+            SearchResults::Tables(tables) => tables,
+            _ => unreachable!("search(SearchLevel::Tables, ..) always returns SearchResults::Tables"),
+        };
+
+        tables.into_iter()
+            .find(|t| t.full_name == full_name)
+            .map(|t| Response::new(table_to_pb(t)))
+            .ok_or_else(|| Status::not_found(format!("table not found: {}", full_name)))
+    }
+}
+
+// Splits a fully-qualified `catalog.schema.table` name into its catalog/schema components, so
+// `get_table` can filter by the same columns as the other RPCs. Returns `None` if `full_name`
+// doesn't have exactly three dot-separated segments.
+fn parse_full_name(full_name: &str) -> Option<(String, String)> {
+    match full_name.split('.').collect::<Vec<_>>().as_slice() {
+        [catalog, schema, _table] => Some((catalog.to_string(), schema.to_string())),
+        _ => None,
+    }
+}
+
+fn table_to_pb(t: crate::api::metastore::Table) -> Table {
+    Table {
+        name: t.name,
+        catalog_name: t.catalog_name,
+        schema_name: t.schema_name,
+        table_type: t.table_type,
+        owner: t.owner,
+        comment: t.comment.unwrap_or_default(),
+        full_name: t.full_name,
+    }
+}
+
+pub async fn serve(addr: SocketAddr, sql_client: SqlClient) -> Result<(), tonic::transport::Error> {
+    let reflection_service = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(FILE_DESCRIPTOR_SET)
+        .build()
+        .expect("failed to build gRPC reflection service");
+
+    log::info!("Starting gRPC catalog server on {}", addr);
+
+    Server::builder()
+        .add_service(UnityCatalogServer::new(CatalogService { sql_client }))
+        .add_service(reflection_service)
+        .serve(addr)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_full_name_splits_catalog_schema_table() {
+        let (catalog, schema) = parse_full_name("main.default.orders").unwrap();
+        assert_eq!(catalog, "main");
+        assert_eq!(schema, "default");
+    }
+
+    #[test]
+    fn parse_full_name_rejects_the_wrong_number_of_segments() {
+        assert!(parse_full_name("main.default").is_none());
+        assert!(parse_full_name("main.default.orders.extra").is_none());
+        assert!(parse_full_name("").is_none());
+    }
+}
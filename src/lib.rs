@@ -0,0 +1,9 @@
+pub mod sql {
+    pub mod sql_client;
+    pub mod filter;
+}
+pub mod api{
+    pub mod metastore;
+    pub mod api_client;
+}
+pub mod grpc;
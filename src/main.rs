@@ -1,45 +1,75 @@
+use clap::{Parser, Subcommand};
 use dotenv::dotenv;
-use log;
 use std::env;
-use reqwest::Error;
-pub mod sql {
-    pub mod sql_client;
-}
-pub mod api{
-    pub mod metastore;
-    pub mod permissions;
-    pub mod api_client;
+use databricks_rust::{api, sql};
+
+#[derive(Parser)]
+#[command(name = "databricks-rust", about = "Sync and query a local mirror of a Databricks Unity Catalog metastore")]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
 }
 
+#[derive(Subcommand)]
+enum Command {
+    /// Sync catalogs, schemas, and tables from Databricks into the local cache
+    Sync,
+    /// Dump the local cache to a portable newline-delimited JSON file
+    Export {
+        #[arg(long)]
+        output: String,
+    },
+    /// Replay a snapshot produced by `export` into a fresh local cache, without contacting Databricks
+    Import {
+        #[arg(long)]
+        input: String,
+    },
+}
 
 #[tokio::main]
-async fn main() -> Result<(), Error> {
+async fn main() -> Result<(), Box<dyn std::error::Error>> {
     dotenv().ok();
-    // env_logger::init();
     env_logger::builder()
     .filter_level(log::LevelFilter::Info)
     .init();
 
-    let db_token: String = env::var("DB_TOKEN").expect("DB_TOKEN not set");
-    let workspace_name: String = env::var("WORKSPACE_NAME").expect("WORKSPACE_NAME not set");
+    let cli: Cli = Cli::parse();
+
     let database_url: String = env::var("DATABASE_URL").expect("DATABASE_URL not set");
     let migrations_path: String = env::var("MIGRATIONS_PATH").expect("MIGRATIONS_PATH not set");
 
+    match cli.command {
+        Command::Sync => {
+            let db_token: String = env::var("DB_TOKEN").expect("DB_TOKEN not set");
+            let workspace_name: String = env::var("WORKSPACE_NAME").expect("WORKSPACE_NAME not set");
 
-    let api_client: api::api_client::APIClient = api::api_client::APIClient {
-        db_token: db_token,
-        workspace_name: workspace_name
-    };
+            let mut api_client: api::api_client::APIClient = api::api_client::APIClient::new(db_token, workspace_name);
+            if let Ok(max_retry_attempts) = env::var("API_MAX_RETRY_ATTEMPTS") {
+                let max_retry_attempts: u32 = max_retry_attempts.parse().expect("API_MAX_RETRY_ATTEMPTS must be a u32");
+                api_client = api_client.with_max_retry_attempts(max_retry_attempts);
+            }
 
-    // Setup SQL
-    let sql_client: sql::sql_client::SqlClient = sql::sql_client::SqlClient::new(&database_url, migrations_path).await.unwrap();
-    let _migrate_results = sql_client.run_migrations().await.unwrap();
-    let metastore_client: api::metastore::MetastoreClient = api::metastore::MetastoreClient{api_client, sql_client};
+            let sql_client: sql::sql_client::SqlClient = sql::sql_client::SqlClient::new(&database_url, migrations_path).await?;
+            sql_client.run_migrations().await?;
+            let metastore_client: api::metastore::MetastoreClient = api::metastore::MetastoreClient { api_client, sql_client };
 
-    let _catalog_update: Result<(), Error> = metastore_client.refresh_catalogs().await;
-    let _schema_update: Result<(), Error> = metastore_client.refresh_all_schemas().await;
-    let _table_update = metastore_client.refresh_all_tables().await;
+            metastore_client.refresh_catalogs().await?;
+            metastore_client.refresh_all_schemas().await?;
+            metastore_client.refresh_all_tables().await?;
+        }
+        Command::Export { output } => {
+            let sql_client: sql::sql_client::SqlClient = sql::sql_client::SqlClient::new(&database_url, migrations_path).await?;
+            sql_client.run_migrations().await?;
+            sql_client.export_all(&output).await?;
+            log::info!("Exported catalog snapshot to {}", output);
+        }
+        Command::Import { input } => {
+            let sql_client: sql::sql_client::SqlClient = sql::sql_client::SqlClient::new(&database_url, migrations_path).await?;
+            sql_client.run_migrations().await?;
+            sql_client.import_all(&input).await?;
+            log::info!("Imported catalog snapshot from {}", input);
+        }
+    }
 
     Ok(())
-
-}
\ No newline at end of file
+}
@@ -0,0 +1,166 @@
+// A small, backend-agnostic WHERE-clause builder used by `SqlClient::search*`. Every predicate is
+// parameterized so callers never need to string-interpolate user-supplied search terms into SQL.
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterValue {
+    Text(String),
+    Bool(bool),
+}
+
+// The filters a caller can apply across catalogs/schemas/tables. Not every field is meaningful at
+// every level: `catalog_name`/`schema_name` are ignored by `search_catalogs`, `schema_name` is
+// ignored by `search_schemas`, etc. See `SqlClient::search` for how a `SearchLevel` maps onto these.
+#[derive(Debug, Clone, Default)]
+pub struct Filters {
+    // Case-insensitive substring match against name/comment/owner.
+    pub term: Option<String>,
+    // Exact match on the owning catalog.
+    pub catalog_name: Option<String>,
+    // Exact match on the owning schema.
+    pub schema_name: Option<String>,
+    pub browse_only: Option<bool>,
+    pub order_by: Option<&'static str>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Default)]
+pub struct FilterBuilder {
+    clauses: Vec<String>,
+    values: Vec<FilterValue>,
+    order_by: Option<&'static str>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+impl FilterBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Case-insensitive `LIKE` across the given columns, OR'd together. `term` is escaped so a
+    // literal `%`/`_`/`\` in the search string is matched literally rather than as a wildcard.
+    pub fn term(mut self, columns: &[&str], term: &str) -> Self {
+        let predicate: String = columns.iter()
+            .map(|c| format!("lower({}) like ? escape '\\'", c))
+            .collect::<Vec<_>>()
+            .join(" or ");
+        self.clauses.push(format!("({})", predicate));
+
+        let escaped: String = term.to_lowercase()
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let pattern: String = format!("%{}%", escaped);
+        for _ in columns {
+            self.values.push(FilterValue::Text(pattern.clone()));
+        }
+        self
+    }
+
+    pub fn eq_text(mut self, column: &str, value: &str) -> Self {
+        self.clauses.push(format!("{} = ?", column));
+        self.values.push(FilterValue::Text(value.to_string()));
+        self
+    }
+
+    pub fn eq_bool(mut self, column: &str, value: bool) -> Self {
+        self.clauses.push(format!("{} = ?", column));
+        self.values.push(FilterValue::Bool(value));
+        self
+    }
+
+    pub fn order_by(mut self, column: &'static str) -> Self {
+        self.order_by = Some(column);
+        self
+    }
+
+    pub fn limit(mut self, limit: i64) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    pub fn offset(mut self, offset: i64) -> Self {
+        self.offset = Some(offset);
+        self
+    }
+
+    // Appends the accumulated WHERE/ORDER BY/LIMIT/OFFSET clauses to `base_query` and returns the
+    // finished SQL alongside the values to bind, in order.
+    pub fn build(self, base_query: &str) -> (String, Vec<FilterValue>) {
+        let mut qry: String = base_query.to_string();
+
+        if !self.clauses.is_empty() {
+            qry.push_str(" where ");
+            qry.push_str(&self.clauses.join(" and "));
+        }
+        if let Some(order_by) = self.order_by {
+            qry.push_str(&format!(" order by {}", order_by));
+        }
+        if let Some(limit) = self.limit {
+            qry.push_str(&format!(" limit {}", limit));
+        }
+        if let Some(offset) = self.offset {
+            qry.push_str(&format!(" offset {}", offset));
+        }
+
+        (qry, self.values)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn term_parameterizes_instead_of_interpolating() {
+        let (qry, values) = FilterBuilder::new()
+            .term(&["name", "comment"], "Orders")
+            .build("select * from tables");
+
+        assert_eq!(
+            qry,
+            "select * from tables where (lower(name) like ? escape '\\' or lower(comment) like ? escape '\\')"
+        );
+        assert_eq!(
+            values,
+            vec![FilterValue::Text("%orders%".to_string()), FilterValue::Text("%orders%".to_string())]
+        );
+    }
+
+    #[test]
+    fn term_escapes_like_wildcards() {
+        let (_, values) = FilterBuilder::new()
+            .term(&["name"], "100%_done\\")
+            .build("select * from tables");
+
+        assert_eq!(values, vec![FilterValue::Text("%100\\%\\_done\\\\%".to_string())]);
+    }
+
+    #[test]
+    fn eq_and_paging_clauses_compose() {
+        let (qry, values) = FilterBuilder::new()
+            .eq_text("catalog_name", "main")
+            .eq_bool("browse_only", false)
+            .order_by("name")
+            .limit(10)
+            .offset(20)
+            .build("select * from schemas");
+
+        assert_eq!(
+            qry,
+            "select * from schemas where catalog_name = ? and browse_only = ? order by name limit 10 offset 20"
+        );
+        assert_eq!(
+            values,
+            vec![FilterValue::Text("main".to_string()), FilterValue::Bool(false)]
+        );
+    }
+
+    #[test]
+    fn build_with_no_clauses_leaves_base_query_untouched() {
+        let (qry, values) = FilterBuilder::new().build("select * from catalogs");
+        assert_eq!(qry, "select * from catalogs");
+        assert!(values.is_empty());
+    }
+}
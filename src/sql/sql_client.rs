@@ -1,34 +1,90 @@
 // https://github.com/launchbadge/sqlx/tree/main/examples/sqlite/todos
 use log;
-use sqlx::migrate::{MigrateError, MigrateDatabase};
-use crate::api::metastore::{CatalogResponse, SchemaResponse, TableResponse, Catalog};
-use sqlx::{Error, Sqlite};
-use sqlx::sqlite::{SqliteQueryResult, SqlitePool};
+use sqlx::migrate::MigrateError;
+use crate::api::metastore::{
+    CatalogResponse, SchemaResponse, TableResponse, Catalog, Schema, Table, Column, TableConstraint,
+    TableDependency,
+};
+#[cfg(test)]
+use crate::api::metastore::{
+    ForeignKeyConstraint, FunctionDependencyRef, PrimaryKeyConstraint, TableDependencyRef,
+};
+use crate::sql::filter::{FilterBuilder, FilterValue, Filters};
+use futures::TryStreamExt;
+use serde::{Deserialize, Serialize};
+use sqlx::any::{install_default_drivers, Any, AnyPoolOptions, AnyQueryResult};
+use sqlx::Error;
+use std::io::{BufRead, BufReader, BufWriter, Write};
 
+#[cfg(feature = "sqlite")]
+use sqlx::migrate::MigrateDatabase;
+
+// Which wire-level database this pool is actually talking to. `sqlx::Any` erases the concrete
+// driver, so anywhere we need backend-specific SQL (upserts, database creation) we dispatch on
+// this instead of hardcoding one database's dialect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DbBackend {
+    Sqlite,
+    Postgres,
+    MySql,
+}
+
+impl DbBackend {
+    fn from_url(database_url: &str) -> Result<Self, Error> {
+        if database_url.starts_with("sqlite:") {
+            #[cfg(feature = "sqlite")]
+            return Ok(DbBackend::Sqlite);
+            #[cfg(not(feature = "sqlite"))]
+            return Err(Error::Configuration("sqlite support requires the \"sqlite\" feature".into()));
+        } else if database_url.starts_with("postgres:") || database_url.starts_with("postgresql:") {
+            #[cfg(feature = "postgres")]
+            return Ok(DbBackend::Postgres);
+            #[cfg(not(feature = "postgres"))]
+            return Err(Error::Configuration("postgres support requires the \"postgres\" feature".into()));
+        } else if database_url.starts_with("mysql:") {
+            #[cfg(feature = "mysql")]
+            return Ok(DbBackend::MySql);
+            #[cfg(not(feature = "mysql"))]
+            return Err(Error::Configuration("mysql support requires the \"mysql\" feature".into()));
+        } else {
+            Err(Error::Configuration(format!("unrecognized DATABASE_URL scheme: {}", database_url).into()))
+        }
+    }
+}
+
+// Flush each record type's buffer to the database as soon as it reaches this many rows, so
+// `import_all` holds a bounded amount of a large snapshot in memory at once instead of the
+// whole file.
+const IMPORT_BATCH_SIZE: usize = 500;
 
 #[derive(Clone)]
 pub struct SqlClient {
-    pub pool: sqlx::Pool<Sqlite>,
+    pub pool: sqlx::Pool<Any>,
     pub migrations_path: String,
+    pub backend: DbBackend,
 
 }
 
 impl SqlClient {
     pub async fn new(database_path: &str, migrations_path: String) -> Result<Self, Error> {
-        // Create SQLite connection options
-        if !Sqlite::database_exists(database_path).await? {
-            // Sqlite::create_database(database_path).await?;
-            match Sqlite::create_database(database_path).await {
+        install_default_drivers();
+
+        let backend: DbBackend = DbBackend::from_url(database_path)?;
+
+        #[cfg(feature = "sqlite")]
+        if backend == DbBackend::Sqlite && !sqlx::Sqlite::database_exists(database_path).await? {
+            match sqlx::Sqlite::create_database(database_path).await {
                 Ok(_) => log::info!("Create db success"),
                 Err(error) => panic!("error: {}", error),
             }
         }
-        let pool: sqlx::Pool<Sqlite> = SqlitePool::connect(database_path).await?;
 
-        Ok(Self { pool, migrations_path})
+        let pool: sqlx::Pool<Any> = AnyPoolOptions::new().connect(database_path).await?;
+
+        Ok(Self { pool, migrations_path, backend })
     }
 
-    pub async fn execute_sql(&self, query: &str) -> Result<SqliteQueryResult, Error> {
+    pub async fn execute_sql(&self, query: &str) -> Result<AnyQueryResult, Error> {
         log::info!("Executing SQL: {}", query);
         let result = sqlx::query(query).execute(&self.pool).await;
         match result {
@@ -41,7 +97,7 @@ impl SqlClient {
                 Err(err)
             }
         }
-    
+
     }
 
     pub async fn run_migrations(&self) -> Result<(), MigrateError> {
@@ -60,122 +116,126 @@ impl SqlClient {
                 panic!("error: {}", error);
             }
         }
-    
+
         log::info!("migration: {:?}", migration_results);
 
         migration_results
     }
 
     pub async fn write_catalogs(&self, catalog_response: CatalogResponse) -> Result<(), sqlx::Error> {
-        // let mut tx = self.pool.begin().await?;
-        // let conn = self.pool.acquire().await?;
-        // let mut tx = conn.begin().await?;
+        let sql: String = catalog_upsert_sql(self.backend);
+        let mut tx = self.pool.begin().await?;
 
         for catalog in catalog_response.catalogs {
             if catalog.catalog_type != "DELTASHARING_CATALOG" && catalog.name != "__databricks_internal" {
-                let _result: SqliteQueryResult = sqlx::query(
-                    "INSERT OR REPLACE INTO catalogs (name, owner, comment, storage_root, provider_name, share_name, enable_predictive_optimization, metastore_id, created_at, created_by, updated_at, updated_by, catalog_type, storage_location, isolation_mode, connection_name, full_name, securable_kind, securable_type, browse_only)
-                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)"
-                )
-                .bind(&catalog.name)
-                .bind(&catalog.owner)
-                .bind(&catalog.comment)
-                .bind(&catalog.storage_root)
-                .bind(&catalog.provider_name)
-                .bind(&catalog.share_name)
-                .bind(&catalog.enable_predictive_optimization)
-                .bind(&catalog.metastore_id)
-                .bind(&catalog.created_at)
-                .bind(&catalog.created_by)
-                .bind(&catalog.updated_at)
-                .bind(&catalog.updated_by)
-                .bind(&catalog.catalog_type)
-                .bind(&catalog.storage_location)
-                .bind(&catalog.isolation_mode)
-                .bind(&catalog.connection_name)
-                .bind(&catalog.full_name)
-                .bind(&catalog.securable_kind)
-                .bind(&catalog.securable_type)
-                .bind(&catalog.browse_only)
-                .execute(&self.pool)
-                // .execute(&mut tx)
+                sqlx::query(&sql)
+                .bind(catalog.name)
+                .bind(catalog.owner)
+                .bind(catalog.comment)
+                .bind(catalog.storage_root)
+                .bind(catalog.provider_name)
+                .bind(catalog.share_name)
+                .bind(catalog.enable_predictive_optimization)
+                .bind(catalog.metastore_id)
+                .bind(catalog.created_at)
+                .bind(catalog.created_by)
+                .bind(catalog.updated_at)
+                .bind(catalog.updated_by)
+                .bind(catalog.catalog_type)
+                .bind(catalog.storage_location)
+                .bind(catalog.isolation_mode)
+                .bind(catalog.connection_name)
+                .bind(catalog.full_name)
+                .bind(catalog.securable_kind)
+                .bind(catalog.securable_type)
+                .bind(catalog.browse_only)
+                .execute(&mut *tx)
                 .await?;
             }
         }
-        // tx.commit().await?;
+        tx.commit().await?;
         Ok(())
     }
-    
+
 
     pub async fn write_schemas(&self, schema_response: SchemaResponse) -> Result<(), sqlx::Error> {
+        let sql: String = schema_upsert_sql(self.backend);
+        let mut tx = self.pool.begin().await?;
+
         if let Some(schemas) = schema_response.schemas {
             for schema in schemas {
                 log::info!("Catalog: {} | Schema: {}", schema.catalog_name, schema.name);
-                let _result = sqlx::query(
-                    "INSERT OR REPLACE INTO schemas (name, catalog_name, owner, comment, storage_root, enable_predictive_optimization, metastore_id, full_name, storage_location, created_at, created_by, updated_at, updated_by, catalog_type, browse_only, schema_id) 
-                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)"
-                )
-                .bind(&schema.name)
-                .bind(&schema.catalog_name)
-                .bind(&schema.owner)
-                .bind(&schema.comment)
-                .bind(&schema.storage_root)
-                .bind(&schema.enable_predictive_optimization)
-                .bind(&schema.metastore_id)
-                .bind(&schema.full_name)
-                .bind(&schema.storage_location)
+                sqlx::query(&sql)
+                .bind(schema.name)
+                .bind(schema.catalog_name)
+                .bind(schema.owner)
+                .bind(schema.comment)
+                .bind(schema.storage_root)
+                .bind(schema.enable_predictive_optimization)
+                .bind(schema.metastore_id)
+                .bind(schema.full_name)
+                .bind(schema.storage_location)
                 .bind(schema.created_at)
-                .bind(&schema.created_by)
-                .bind(&schema.updated_at)
-                .bind(&schema.updated_by)
-                .bind(&schema.catalog_type)
-                .bind(&schema.browse_only)
-                .bind(&schema.schema_id)
-                .execute(&self.pool)
+                .bind(schema.created_by)
+                .bind(schema.updated_at)
+                .bind(schema.updated_by)
+                .bind(schema.catalog_type)
+                .bind(schema.browse_only)
+                .bind(schema.schema_id)
+                .execute(&mut *tx)
                 .await?;
-
-
             }
-        }    
+        }
+        tx.commit().await?;
         Ok(())
     }
 
     pub async fn write_tables(&self, table_response: TableResponse) -> Result<(), sqlx::Error> {
         log::info!("Writing Tables!");
+        let table_sql: String = table_upsert_sql(self.backend);
+        let column_sql: String = column_upsert_sql(self.backend);
+        let constraint_sql: String = table_constraint_upsert_sql(self.backend);
+        let dependency_sql: String = table_dependency_upsert_sql(self.backend);
+        let mut tx = self.pool.begin().await?;
+
         if let Some(tables) = table_response.tables {
             for table in tables {
                 log::info!(" Catalog: {} | Schema: {} | Table: {}", table.catalog_name, table.schema_name, table.name);
-                let result = sqlx::query(
-                    "INSERT OR REPLACE INTO tables (name, catalog_name, schema_name, table_type, data_source_format, storage_location, view_definition, sql_path, owner, comment, storage_credential_name, enable_predictive_optimization, metastore_id, full_name, data_access_configuration_id, created_at, created_by, updated_at, updated_by, deleted_at, table_id, access_point, pipeline_id, browse_only) 
-                    VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22, $23, $24)"
-                )
-                .bind(&table.name)
-                .bind(&table.catalog_name)
-                .bind(&table.schema_name)
-                .bind(&table.table_type)
-                .bind(&table.data_source_format)
-                .bind(&table.storage_location)
-                .bind(&table.view_definition)
-                .bind(&table.sql_path)
-                .bind(&table.owner)
-                .bind(&table.comment)
-                .bind(&table.storage_credential_name)
-                .bind(&table.enable_predictive_optimization)
-                .bind(&table.metastore_id)
-                .bind(&table.full_name)
-                .bind(&table.data_access_configuration_id)
-                .bind(&table.created_at)
-                .bind(&table.created_by)
-                .bind(&table.updated_at)
-                .bind(&table.updated_by)
-                .bind(&table.deleted_at)
-                .bind(&table.table_id)
-                .bind(&table.access_point)
-                .bind(&table.pipeline_id)
-                .bind(&table.browse_only)
-                .execute(&self.pool)
+
+                let table_id: String = table.table_id.clone();
+                let full_name: String = table.full_name.clone();
+                let columns: Option<Vec<Column>> = table.columns.clone();
+                let table_constraints: Option<Vec<TableConstraint>> = table.table_constraints.clone();
+                let dependencies: Option<Vec<TableDependency>> = table.dependencies.clone();
+
+                let result = sqlx::query(&table_sql)
+                .bind(table.name)
+                .bind(table.catalog_name)
+                .bind(table.schema_name)
+                .bind(table.table_type)
+                .bind(table.data_source_format)
+                .bind(table.storage_location)
+                .bind(table.view_definition)
+                .bind(table.sql_path)
+                .bind(table.owner)
+                .bind(table.comment)
+                .bind(table.storage_credential_name)
+                .bind(table.enable_predictive_optimization)
+                .bind(table.metastore_id)
+                .bind(table.full_name)
+                .bind(table.data_access_configuration_id)
+                .bind(table.created_at)
+                .bind(table.created_by)
+                .bind(table.updated_at)
+                .bind(table.updated_by)
+                .bind(table.deleted_at)
+                .bind(table.table_id)
+                .bind(table.access_point)
+                .bind(table.pipeline_id)
+                .bind(table.browse_only)
+                .execute(&mut *tx)
                 .await;
-                
+
                 match result {
                     Ok(res) => {
                         log::info!("--------------- {:?}", res);
@@ -184,28 +244,687 @@ impl SqlClient {
                         log::error!("Error executing SQL query: {}", err);
                         return Err(err);
                     }
-                } 
+                }
+
+                if let Some(columns) = columns {
+                    for column in columns {
+                        sqlx::query(&column_sql)
+                        .bind(&table_id)
+                        .bind(column.name)
+                        .bind(column.type_text)
+                        .bind(column.type_name)
+                        .bind(column.type_precision)
+                        .bind(column.type_scale)
+                        .bind(column.type_json)
+                        .bind(column.position)
+                        .bind(column.comment)
+                        .bind(column.nullable)
+                        .bind(column.partition_index)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
+
+                if let Some(table_constraints) = table_constraints {
+                    for constraint in table_constraints {
+                        let (constraint_name, constraint_type, child_columns, parent_table, parent_columns) =
+                            flatten_constraint(&constraint);
+
+                        sqlx::query(&constraint_sql)
+                        .bind(&full_name)
+                        .bind(constraint_name)
+                        .bind(constraint_type)
+                        .bind(child_columns)
+                        .bind(parent_table)
+                        .bind(parent_columns)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
+
+                if let Some(dependencies) = dependencies {
+                    for (ordinal, dependency) in dependencies.into_iter().enumerate() {
+                        let (dependency_type, reference) = flatten_dependency(&dependency);
+
+                        sqlx::query(&dependency_sql)
+                        .bind(&table_id)
+                        .bind(ordinal as i64)
+                        .bind(dependency_type)
+                        .bind(reference)
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
             }
         }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn search_catalogs(&self, filters: &Filters) -> Result<Vec<Catalog>, sqlx::Error> {
+        let base: &str = "select name, owner, comment, storage_root, provider_name, share_name, enable_predictive_optimization, metastore_id, created_at, created_by, updated_at, updated_by, catalog_type, storage_location, isolation_mode, connection_name, full_name, securable_kind, securable_type, browse_only from catalogs";
+
+        let mut builder: FilterBuilder = FilterBuilder::new();
+        if let Some(term) = &filters.term {
+            builder = builder.term(&["name", "comment", "owner"], term);
+        }
+        builder = apply_paging(builder, filters);
+
+        let (qry, values) = builder.build(base);
+        fetch_filtered::<Catalog>(&self.pool, &qry, values).await
+    }
+
+    // Read back cached schemas, optionally scoped to one catalog. Used by the `search` entry
+    // point, which also backs the gRPC `ListSchemas` RPC.
+    pub async fn search_schemas(&self, filters: &Filters) -> Result<Vec<Schema>, sqlx::Error> {
+        let base: &str = "select name, catalog_name, owner, comment, storage_root, enable_predictive_optimization, metastore_id, full_name, storage_location, created_at, created_by, updated_at, updated_by, catalog_type, browse_only, schema_id from schemas";
+
+        let mut builder: FilterBuilder = FilterBuilder::new();
+        if let Some(term) = &filters.term {
+            builder = builder.term(&["name", "comment", "owner"], term);
+        }
+        if let Some(catalog_name) = &filters.catalog_name {
+            builder = builder.eq_text("catalog_name", catalog_name);
+        }
+        builder = apply_paging(builder, filters);
+
+        let (qry, values) = builder.build(base);
+        fetch_filtered::<Schema>(&self.pool, &qry, values).await
+    }
+
+    // Read back cached tables, optionally scoped to a catalog and/or schema. Used by the
+    // `search` entry point, which also backs the gRPC `ListTables`/`GetTable` RPCs.
+    pub async fn search_tables(&self, filters: &Filters) -> Result<Vec<Table>, sqlx::Error> {
+        let base: &str = "select name, catalog_name, schema_name, table_type, data_source_format, storage_location, view_definition, sql_path, owner, comment, storage_credential_name, enable_predictive_optimization, metastore_id, full_name, data_access_configuration_id, created_at, created_by, updated_at, updated_by, deleted_at, table_id, access_point, pipeline_id, browse_only from tables";
+
+        let mut builder: FilterBuilder = FilterBuilder::new();
+        if let Some(term) = &filters.term {
+            builder = builder.term(&["name", "comment", "owner"], term);
+        }
+        if let Some(catalog_name) = &filters.catalog_name {
+            builder = builder.eq_text("catalog_name", catalog_name);
+        }
+        if let Some(schema_name) = &filters.schema_name {
+            builder = builder.eq_text("schema_name", schema_name);
+        }
+        if let Some(browse_only) = filters.browse_only {
+            builder = builder.eq_bool("browse_only", browse_only);
+        }
+        builder = apply_paging(builder, filters);
+
+        let (qry, values) = builder.build(base);
+        fetch_filtered::<Table>(&self.pool, &qry, values).await
+    }
+
+    // Single entry point so callers can search across object types with the same `Filters`,
+    // rather than knowing which of the three `search_*` methods to call.
+    pub async fn search(&self, level: SearchLevel, filters: Filters) -> Result<SearchResults, sqlx::Error> {
+        match level {
+            SearchLevel::Catalogs => Ok(SearchResults::Catalogs(self.search_catalogs(&filters).await?)),
+            SearchLevel::Schemas => Ok(SearchResults::Schemas(self.search_schemas(&filters).await?)),
+            SearchLevel::Tables => Ok(SearchResults::Tables(self.search_tables(&filters).await?)),
+        }
+    }
+
+    // Stream every cached catalog/schema/table (and their columns/constraints/dependencies) out to
+    // `path` as newline-delimited JSON, one record per line, so a metastore snapshot can be moved
+    // between environments or diffed offline without ever touching Databricks.
+    pub async fn export_all(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let mut writer = BufWriter::new(std::fs::File::create(path)?);
+
+        let catalogs_sql = format!("select {} from catalogs", CATALOG_COLUMNS.join(", "));
+        let mut catalogs = sqlx::query_as::<_, Catalog>(&catalogs_sql).fetch(&self.pool);
+        while let Some(catalog) = catalogs.try_next().await? {
+            write_record(&mut writer, &ExportRecord::Catalog(catalog))?;
+        }
+        drop(catalogs);
+
+        let schemas_sql = format!("select {} from schemas", SCHEMA_COLUMNS.join(", "));
+        let mut schemas = sqlx::query_as::<_, Schema>(&schemas_sql).fetch(&self.pool);
+        while let Some(schema) = schemas.try_next().await? {
+            write_record(&mut writer, &ExportRecord::Schema(schema))?;
+        }
+        drop(schemas);
+
+        let tables_sql = format!("select {} from tables", TABLE_COLUMNS.join(", "));
+        let mut tables = sqlx::query_as::<_, Table>(&tables_sql).fetch(&self.pool);
+        while let Some(table) = tables.try_next().await? {
+            write_record(&mut writer, &ExportRecord::Table(table))?;
+        }
+        drop(tables);
+
+        let columns_sql = format!("select {} from columns", COLUMN_COLUMNS.join(", "));
+        let mut columns = sqlx::query_as::<_, ColumnRecord>(&columns_sql).fetch(&self.pool);
+        while let Some(column) = columns.try_next().await? {
+            write_record(&mut writer, &ExportRecord::Column(column))?;
+        }
+        drop(columns);
+
+        let constraints_sql = format!("select {} from table_constraints", TABLE_CONSTRAINT_COLUMNS.join(", "));
+        let mut constraints = sqlx::query_as::<_, TableConstraintRecord>(&constraints_sql).fetch(&self.pool);
+        while let Some(constraint) = constraints.try_next().await? {
+            write_record(&mut writer, &ExportRecord::TableConstraint(constraint))?;
+        }
+        drop(constraints);
+
+        let dependencies_sql = format!("select {} from table_dependencies", TABLE_DEPENDENCY_COLUMNS.join(", "));
+        let mut dependencies = sqlx::query_as::<_, TableDependencyRecord>(&dependencies_sql).fetch(&self.pool);
+        while let Some(dependency) = dependencies.try_next().await? {
+            write_record(&mut writer, &ExportRecord::TableDependency(dependency))?;
+        }
+        drop(dependencies);
+
+        writer.flush()?;
+        Ok(())
+    }
+
+    // Replay a snapshot written by `export_all` into this (normally freshly-migrated,
+    // empty) database, reading the file one line at a time. Rows are grouped by record type and
+    // handed to `write_catalogs`/`write_schemas`/`write_tables` (and the bulk column/constraint/
+    // dependency importers below) as soon as a type's buffer reaches `IMPORT_BATCH_SIZE`, so a
+    // large catalog import runs a bounded number of rows through memory and a handful of
+    // transactions per type instead of buffering the whole file before the first write.
+    pub async fn import_all(&self, path: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let reader = BufReader::new(std::fs::File::open(path)?);
+
+        let mut catalogs: Vec<Catalog> = Vec::new();
+        let mut schemas: Vec<Schema> = Vec::new();
+        let mut tables: Vec<Table> = Vec::new();
+        let mut columns: Vec<ColumnRecord> = Vec::new();
+        let mut constraints: Vec<TableConstraintRecord> = Vec::new();
+        let mut dependencies: Vec<TableDependencyRecord> = Vec::new();
+
+        for line in reader.lines() {
+            let line: String = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+
+            match serde_json::from_str::<ExportRecord>(&line)? {
+                ExportRecord::Catalog(catalog) => {
+                    catalogs.push(catalog);
+                    if catalogs.len() >= IMPORT_BATCH_SIZE {
+                        self.write_catalogs(CatalogResponse { catalogs: std::mem::take(&mut catalogs), next_page_token: None }).await?;
+                    }
+                }
+                ExportRecord::Schema(schema) => {
+                    schemas.push(schema);
+                    if schemas.len() >= IMPORT_BATCH_SIZE {
+                        self.write_schemas(SchemaResponse { schemas: Some(std::mem::take(&mut schemas)), next_page_token: None }).await?;
+                    }
+                }
+                ExportRecord::Table(table) => {
+                    tables.push(table);
+                    if tables.len() >= IMPORT_BATCH_SIZE {
+                        self.write_tables(TableResponse { tables: Some(std::mem::take(&mut tables)), next_page_token: None }).await?;
+                    }
+                }
+                ExportRecord::Column(column) => {
+                    columns.push(column);
+                    if columns.len() >= IMPORT_BATCH_SIZE {
+                        self.import_column_records(std::mem::take(&mut columns)).await?;
+                    }
+                }
+                ExportRecord::TableConstraint(constraint) => {
+                    constraints.push(constraint);
+                    if constraints.len() >= IMPORT_BATCH_SIZE {
+                        self.import_table_constraint_records(std::mem::take(&mut constraints)).await?;
+                    }
+                }
+                ExportRecord::TableDependency(dependency) => {
+                    dependencies.push(dependency);
+                    if dependencies.len() >= IMPORT_BATCH_SIZE {
+                        self.import_table_dependency_records(std::mem::take(&mut dependencies)).await?;
+                    }
+                }
+            }
+        }
+
+        if !catalogs.is_empty() {
+            self.write_catalogs(CatalogResponse { catalogs, next_page_token: None }).await?;
+        }
+        if !schemas.is_empty() {
+            self.write_schemas(SchemaResponse { schemas: Some(schemas), next_page_token: None }).await?;
+        }
+        if !tables.is_empty() {
+            self.write_tables(TableResponse { tables: Some(tables), next_page_token: None }).await?;
+        }
+        if !columns.is_empty() {
+            self.import_column_records(columns).await?;
+        }
+        if !constraints.is_empty() {
+            self.import_table_constraint_records(constraints).await?;
+        }
+        if !dependencies.is_empty() {
+            self.import_table_dependency_records(dependencies).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn import_column_records(&self, records: Vec<ColumnRecord>) -> Result<(), sqlx::Error> {
+        let sql: String = column_upsert_sql(self.backend);
+        let mut tx = self.pool.begin().await?;
+
+        for record in records {
+            sqlx::query(&sql)
+            .bind(record.table_id)
+            .bind(record.name)
+            .bind(record.type_text)
+            .bind(record.type_name)
+            .bind(record.type_precision)
+            .bind(record.type_scale)
+            .bind(record.type_json)
+            .bind(record.position)
+            .bind(record.comment)
+            .bind(record.nullable)
+            .bind(record.partition_index)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn import_table_constraint_records(&self, records: Vec<TableConstraintRecord>) -> Result<(), sqlx::Error> {
+        let sql: String = table_constraint_upsert_sql(self.backend);
+        let mut tx = self.pool.begin().await?;
+
+        for record in records {
+            sqlx::query(&sql)
+            .bind(record.full_name)
+            .bind(record.constraint_name)
+            .bind(record.constraint_type)
+            .bind(record.child_columns)
+            .bind(record.parent_table)
+            .bind(record.parent_columns)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
         Ok(())
     }
 
-    pub async fn search_catalogs(&self, search_term: Option<&str>) -> Result<Vec<Catalog>, sqlx::Error> {
-        let mut qry: String = String::from("select name from catalogs");
+    async fn import_table_dependency_records(&self, records: Vec<TableDependencyRecord>) -> Result<(), sqlx::Error> {
+        let sql: String = table_dependency_upsert_sql(self.backend);
+        let mut tx = self.pool.begin().await?;
 
-        if let Some(term) = search_term {
-            qry.push_str(&format!(" where like %{}%", term));
+        for record in records {
+            sqlx::query(&sql)
+            .bind(record.table_id)
+            .bind(record.ordinal)
+            .bind(record.dependency_type)
+            .bind(record.dependency_reference)
+            .execute(&mut *tx)
+            .await?;
         }
 
-        let catalogs: Vec<Catalog> = sqlx::query_as::<_, Catalog>(
-            &qry
-        )
-        // .bind(query)
-        .fetch_all(&self.pool)
-        .await?;
+        tx.commit().await?;
+        Ok(())
+    }
+
+}
+
+fn write_record(writer: &mut BufWriter<std::fs::File>, record: &ExportRecord) -> Result<(), Box<dyn std::error::Error>> {
+    serde_json::to_writer(&mut *writer, record)?;
+    writer.write_all(b"\n")?;
+    Ok(())
+}
+
+// One line of an `export_all`/`import_all` snapshot file. Tagged so a single NDJSON file can carry
+// every object type and `import_all` can dispatch on it without a separate file per table.
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(tag = "record_type", content = "data", rename_all = "snake_case")]
+enum ExportRecord {
+    Catalog(Catalog),
+    Schema(Schema),
+    Table(Table),
+    Column(ColumnRecord),
+    TableConstraint(TableConstraintRecord),
+    TableDependency(TableDependencyRecord),
+}
+
+// Flat row shapes for the child tables added alongside column/constraint/dependency ingestion.
+// These mirror the SQL schema directly (rather than the nested API shapes in `metastore::Column`
+// etc.) since that's what `export_all` reads back with `SELECT` and what `import_all` re-inserts.
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+struct ColumnRecord {
+    table_id: String,
+    name: String,
+    type_text: Option<String>,
+    type_name: Option<String>,
+    type_precision: Option<i64>,
+    type_scale: Option<i64>,
+    type_json: Option<String>,
+    position: Option<i64>,
+    comment: Option<String>,
+    nullable: Option<bool>,
+    partition_index: Option<i64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+struct TableConstraintRecord {
+    full_name: String,
+    constraint_name: String,
+    constraint_type: String,
+    child_columns: String,
+    parent_table: Option<String>,
+    parent_columns: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+struct TableDependencyRecord {
+    table_id: String,
+    ordinal: i64,
+    dependency_type: String,
+    dependency_reference: String,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SearchLevel {
+    Catalogs,
+    Schemas,
+    Tables,
+}
+
+#[derive(Debug)]
+pub enum SearchResults {
+    Catalogs(Vec<Catalog>),
+    Schemas(Vec<Schema>),
+    Tables(Vec<Table>),
+}
+
+fn apply_paging(mut builder: FilterBuilder, filters: &Filters) -> FilterBuilder {
+    if let Some(order_by) = filters.order_by {
+        builder = builder.order_by(order_by);
+    }
+    if let Some(limit) = filters.limit {
+        builder = builder.limit(limit);
+    }
+    if let Some(offset) = filters.offset {
+        builder = builder.offset(offset);
+    }
+    builder
+}
 
-        Ok(catalogs)
+async fn fetch_filtered<O>(pool: &sqlx::Pool<Any>, qry: &str, values: Vec<FilterValue>) -> Result<Vec<O>, sqlx::Error>
+where
+    O: for<'r> sqlx::FromRow<'r, sqlx::any::AnyRow> + Send + Unpin,
+{
+    let mut query = sqlx::query_as::<_, O>(qry);
+    for value in values {
+        query = match value {
+            FilterValue::Text(text) => query.bind(text),
+            FilterValue::Bool(flag) => query.bind(flag),
+        };
     }
 
+    query.fetch_all(pool).await
 }
 
+// Column list shared between the catalogs INSERT and its upsert update lists, kept in one
+// place so the VALUES placeholders, MySQL's VALUES() list, and Postgres/SQLite's excluded.* list
+// can't drift out of sync with each other.
+const CATALOG_COLUMNS: &[&str] = &[
+    "name", "owner", "comment", "storage_root", "provider_name", "share_name",
+    "enable_predictive_optimization", "metastore_id", "created_at", "created_by", "updated_at",
+    "updated_by", "catalog_type", "storage_location", "isolation_mode", "connection_name",
+    "full_name", "securable_kind", "securable_type", "browse_only",
+];
+
+const SCHEMA_COLUMNS: &[&str] = &[
+    "name", "catalog_name", "owner", "comment", "storage_root", "enable_predictive_optimization",
+    "metastore_id", "full_name", "storage_location", "created_at", "created_by", "updated_at",
+    "updated_by", "catalog_type", "browse_only", "schema_id",
+];
+
+const TABLE_COLUMNS: &[&str] = &[
+    "name", "catalog_name", "schema_name", "table_type", "data_source_format", "storage_location",
+    "view_definition", "sql_path", "owner", "comment", "storage_credential_name",
+    "enable_predictive_optimization", "metastore_id", "full_name", "data_access_configuration_id",
+    "created_at", "created_by", "updated_at", "updated_by", "deleted_at", "table_id",
+    "access_point", "pipeline_id", "browse_only",
+];
+
+fn upsert_sql(table: &str, columns: &[&str], conflict_keys: &[&str], backend: DbBackend) -> String {
+    let column_list: String = columns.join(", ");
+    let placeholders: String = columns.iter().map(|_| "?").collect::<Vec<_>>().join(", ");
+
+    match backend {
+        DbBackend::MySql => {
+            let assignments: String = columns.iter()
+                .filter(|c| !conflict_keys.contains(c))
+                .map(|c| format!("{} = VALUES({})", c, c))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "INSERT INTO {table} ({column_list}) VALUES ({placeholders}) ON DUPLICATE KEY UPDATE {assignments}"
+            )
+        }
+        DbBackend::Sqlite | DbBackend::Postgres => {
+            let conflict_key_list: String = conflict_keys.join(", ");
+            let assignments: String = columns.iter()
+                .filter(|c| !conflict_keys.contains(c))
+                .map(|c| format!("{} = excluded.{}", c, c))
+                .collect::<Vec<_>>()
+                .join(", ");
+            format!(
+                "INSERT INTO {table} ({column_list}) VALUES ({placeholders}) ON CONFLICT ({conflict_key_list}) DO UPDATE SET {assignments}"
+            )
+        }
+    }
+}
+
+fn catalog_upsert_sql(backend: DbBackend) -> String {
+    upsert_sql("catalogs", CATALOG_COLUMNS, &["name"], backend)
+}
+
+fn schema_upsert_sql(backend: DbBackend) -> String {
+    upsert_sql("schemas", SCHEMA_COLUMNS, &["full_name"], backend)
+}
+
+fn table_upsert_sql(backend: DbBackend) -> String {
+    upsert_sql("tables", TABLE_COLUMNS, &["full_name"], backend)
+}
+
+const COLUMN_COLUMNS: &[&str] = &[
+    "table_id", "name", "type_text", "type_name", "type_precision", "type_scale", "type_json",
+    "position", "comment", "nullable", "partition_index",
+];
+
+const TABLE_CONSTRAINT_COLUMNS: &[&str] = &[
+    "full_name", "constraint_name", "constraint_type", "child_columns", "parent_table", "parent_columns",
+];
+
+const TABLE_DEPENDENCY_COLUMNS: &[&str] = &[
+    "table_id", "ordinal", "dependency_type", "dependency_reference",
+];
+
+fn column_upsert_sql(backend: DbBackend) -> String {
+    upsert_sql("columns", COLUMN_COLUMNS, &["table_id", "name"], backend)
+}
+
+fn table_constraint_upsert_sql(backend: DbBackend) -> String {
+    upsert_sql("table_constraints", TABLE_CONSTRAINT_COLUMNS, &["full_name", "constraint_name"], backend)
+}
+
+fn table_dependency_upsert_sql(backend: DbBackend) -> String {
+    upsert_sql("table_dependencies", TABLE_DEPENDENCY_COLUMNS, &["table_id", "ordinal"], backend)
+}
+
+// Databricks reports exactly one constraint variant per `TableConstraint`; flatten it into the
+// (name, type, child_columns, parent_table, parent_columns) shape the `table_constraints` table
+// stores, leaving the columns that don't apply to this variant `None`.
+fn flatten_constraint(constraint: &TableConstraint) -> (String, &'static str, String, Option<String>, Option<String>) {
+    if let Some(pk) = &constraint.primary_key_constraint {
+        return (pk.name.clone(), "PRIMARY_KEY", pk.child_columns.join(","), None, None);
+    }
+    if let Some(fk) = &constraint.foreign_key_constraint {
+        return (
+            fk.name.clone(),
+            "FOREIGN_KEY",
+            fk.child_columns.join(","),
+            Some(fk.parent_table.clone()),
+            Some(fk.parent_columns.join(",")),
+        );
+    }
+    if let Some(named) = &constraint.named_table_constraint {
+        return (named.name.clone(), "NAMED", String::new(), None, None);
+    }
+    (String::new(), "UNKNOWN", String::new(), None, None)
+}
+
+fn flatten_dependency(dependency: &TableDependency) -> (&'static str, String) {
+    if let Some(table) = &dependency.table {
+        return ("TABLE", table.table_full_name.clone());
+    }
+    if let Some(function) = &dependency.function {
+        return ("FUNCTION", function.function_full_name.clone());
+    }
+    ("UNKNOWN", String::new())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upsert_sql_differs_per_backend() {
+        let mysql: String = upsert_sql("catalogs", &["name", "owner"], &["name"], DbBackend::MySql);
+        assert_eq!(
+            mysql,
+            "INSERT INTO catalogs (name, owner) VALUES (?, ?) ON DUPLICATE KEY UPDATE owner = VALUES(owner)"
+        );
+
+        let sqlite: String = upsert_sql("catalogs", &["name", "owner"], &["name"], DbBackend::Sqlite);
+        assert_eq!(
+            sqlite,
+            "INSERT INTO catalogs (name, owner) VALUES (?, ?) ON CONFLICT (name) DO UPDATE SET owner = excluded.owner"
+        );
+
+        let postgres: String = upsert_sql("catalogs", &["name", "owner"], &["name"], DbBackend::Postgres);
+        assert_eq!(postgres, sqlite);
+    }
+
+    #[test]
+    fn upsert_sql_excludes_conflict_keys_from_assignments() {
+        let sql: String = upsert_sql(
+            "table_constraints",
+            TABLE_CONSTRAINT_COLUMNS,
+            &["full_name", "constraint_name"],
+            DbBackend::Sqlite,
+        );
+        assert!(!sql.contains("full_name = excluded.full_name"));
+        assert!(!sql.contains("constraint_name = excluded.constraint_name"));
+        assert!(sql.contains("constraint_type = excluded.constraint_type"));
+    }
+
+    #[test]
+    fn flatten_constraint_picks_the_populated_variant() {
+        let primary_key = TableConstraint {
+            primary_key_constraint: Some(PrimaryKeyConstraint {
+                name: "pk_id".to_string(),
+                child_columns: vec!["id".to_string()],
+            }),
+            foreign_key_constraint: None,
+            named_table_constraint: None,
+        };
+        assert_eq!(
+            flatten_constraint(&primary_key),
+            ("pk_id".to_string(), "PRIMARY_KEY", "id".to_string(), None, None)
+        );
+
+        let foreign_key = TableConstraint {
+            primary_key_constraint: None,
+            foreign_key_constraint: Some(ForeignKeyConstraint {
+                name: "fk_parent".to_string(),
+                child_columns: vec!["parent_id".to_string()],
+                parent_table: "main.default.parent".to_string(),
+                parent_columns: vec!["id".to_string()],
+            }),
+            named_table_constraint: None,
+        };
+        assert_eq!(
+            flatten_constraint(&foreign_key),
+            (
+                "fk_parent".to_string(),
+                "FOREIGN_KEY",
+                "parent_id".to_string(),
+                Some("main.default.parent".to_string()),
+                Some("id".to_string()),
+            )
+        );
+
+        let empty = TableConstraint {
+            primary_key_constraint: None,
+            foreign_key_constraint: None,
+            named_table_constraint: None,
+        };
+        assert_eq!(
+            flatten_constraint(&empty),
+            (String::new(), "UNKNOWN", String::new(), None, None)
+        );
+    }
+
+    #[test]
+    fn flatten_dependency_picks_the_populated_variant() {
+        let table_dependency = TableDependency {
+            table: Some(TableDependencyRef { table_full_name: "main.default.source".to_string() }),
+            function: None,
+        };
+        assert_eq!(
+            flatten_dependency(&table_dependency),
+            ("TABLE", "main.default.source".to_string())
+        );
+
+        let function_dependency = TableDependency {
+            table: None,
+            function: Some(FunctionDependencyRef { function_full_name: "main.default.my_fn".to_string() }),
+        };
+        assert_eq!(
+            flatten_dependency(&function_dependency),
+            ("FUNCTION", "main.default.my_fn".to_string())
+        );
+
+        let empty = TableDependency { table: None, function: None };
+        assert_eq!(flatten_dependency(&empty), ("UNKNOWN", String::new()));
+    }
+
+    // Round-trips an `ExportRecord` through `write_record`'s NDJSON format the same way
+    // `import_all` reads it back: one `serde_json::from_str` per line. `import_all`/`export_all`
+    // themselves need a migrated database to exercise (see `run_migrations`'s `migrations_path`,
+    // which this repo doesn't commit fixtures for), so this covers the line format they share.
+    #[test]
+    fn export_record_round_trips_through_the_ndjson_line_format() {
+        let path = std::env::temp_dir().join("sql_client_export_record_round_trip_test.ndjson");
+
+        let record = ExportRecord::TableDependency(TableDependencyRecord {
+            table_id: "tbl-1".to_string(),
+            ordinal: 0,
+            dependency_type: "TABLE".to_string(),
+            dependency_reference: "main.default.source".to_string(),
+        });
+
+        {
+            let mut writer = BufWriter::new(std::fs::File::create(&path).unwrap());
+            write_record(&mut writer, &record).unwrap();
+            writer.flush().unwrap();
+        }
+
+        let reader = BufReader::new(std::fs::File::open(&path).unwrap());
+        let lines: Vec<String> = reader.lines().collect::<Result<_, _>>().unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(lines.len(), 1);
+        let parsed: ExportRecord = serde_json::from_str(&lines[0]).unwrap();
+        match parsed {
+            ExportRecord::TableDependency(dependency) => {
+                assert_eq!(dependency.table_id, "tbl-1");
+                assert_eq!(dependency.dependency_reference, "main.default.source");
+            }
+            other => panic!("expected ExportRecord::TableDependency, got {:?}", other),
+        }
+    }
+}